@@ -1,54 +1,151 @@
 use crate::{
-    h_stack, prelude::*, v_stack, KeyBinding, Label, List, ListItem, ListSeparator, ListSubHeader,
+    h_stack, prelude::*, v_stack, KeyBinding, Label, ListItem, ListSeparator, ListSubHeader,
 };
 use gpui::{
     px, Action, AppContext, DismissEvent, Div, EventEmitter, FocusHandle, FocusableView,
-    IntoElement, Render, View, VisualContext,
+    IntoElement, KeyDownEvent, Pixels, Render, ScrollHandle, View, VisualContext,
 };
 use menu::{SelectFirst, SelectLast, SelectNext, SelectPrev};
+use serde::{Deserialize, Serialize};
 use std::rc::Rc;
 
-pub enum ContextMenuItem {
+pub enum ContextMenuItem<T> {
     Separator,
     Header(SharedString),
     Entry {
         label: SharedString,
         handler: Rc<dyn Fn(&mut WindowContext)>,
         key_binding: Option<KeyBinding>,
+        disabled: bool,
+    },
+    Value {
+        label: SharedString,
+        value: T,
+    },
+    Toggle {
+        label: SharedString,
+        checked: bool,
+        handler: Rc<dyn Fn(&mut WindowContext)>,
     },
 }
 
-pub struct ContextMenu {
-    items: Vec<ContextMenuItem>,
+/// A declarative mirror of [`ContextMenuItem`] that can be written to and
+/// read from settings/extension JSON, with entries referencing an [`Action`]
+/// by its registered name instead of embedding a boxed closure. Resolve a
+/// `Vec<MenuItemSpec>` into a real menu with [`ContextMenu::from_spec`].
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MenuItemSpec {
+    Separator,
+    Header { label: SharedString },
+    Entry { label: SharedString, action: String },
+}
+
+/// Emitted alongside [`DismissEvent`] when a [`ContextMenuItem::Value`] entry
+/// is confirmed or clicked, carrying the value the user chose.
+pub struct MenuSelected<T>(pub T);
+
+pub struct ContextMenu<T = ()> {
+    items: Vec<ContextMenuItem<T>>,
     focus_handle: FocusHandle,
     selected_index: Option<usize>,
+    max_height: Option<Pixels>,
+    scroll_handle: ScrollHandle,
+    filterable: bool,
+    filter: String,
 }
 
-impl FocusableView for ContextMenu {
+impl<T: 'static> FocusableView for ContextMenu<T> {
     fn focus_handle(&self, _cx: &AppContext) -> FocusHandle {
         self.focus_handle.clone()
     }
 }
 
-impl EventEmitter<DismissEvent> for ContextMenu {}
+impl<T: 'static> EventEmitter<DismissEvent> for ContextMenu<T> {}
+impl<T: 'static> EventEmitter<MenuSelected<T>> for ContextMenu<T> {}
+
+impl<T: 'static> ContextMenu<T> {
+    fn empty(cx: &mut WindowContext) -> Self {
+        Self {
+            items: Default::default(),
+            focus_handle: cx.focus_handle(),
+            selected_index: None,
+            max_height: None,
+            scroll_handle: ScrollHandle::new(),
+            filterable: false,
+            filter: String::new(),
+        }
+    }
+}
 
-impl ContextMenu {
+impl ContextMenu<()> {
+    /// Builds a unit-typed menu using the closure/action API (`entry`,
+    /// `action`, `header`, ...). This is the entry point nearly every caller
+    /// wants; use [`ContextMenu::build_typed`] for menus whose selection
+    /// should emit a typed value via [`MenuSelected`] instead. Kept as an
+    /// inherent method on the concrete `ContextMenu<()>` (rather than on the
+    /// generic `impl<T>`) so existing call sites keep inferring `T = ()`
+    /// without needing to annotate it.
     pub fn build(
         cx: &mut WindowContext,
         f: impl FnOnce(Self, &mut WindowContext) -> Self,
     ) -> View<Self> {
         // let handle = cx.view().downgrade();
-        cx.build_view(|cx| {
-            f(
-                Self {
-                    items: Default::default(),
-                    focus_handle: cx.focus_handle(),
-                    selected_index: None,
-                },
-                cx,
-            )
+        cx.build_view(|cx| f(Self::empty(cx), cx))
+    }
+
+    /// Builds a menu from a declarative [`MenuItemSpec`] list, resolving each
+    /// named action via the action registry and wiring up
+    /// [`KeyBinding::for_action`]. This lets menus be defined in settings or
+    /// extension JSON instead of being hardcoded in Rust. Entries whose
+    /// action name isn't registered are skipped.
+    pub fn from_spec(spec: Vec<MenuItemSpec>, cx: &mut WindowContext) -> View<Self> {
+        Self::build(cx, |mut menu, cx| {
+            for item in spec {
+                menu = match item {
+                    MenuItemSpec::Separator => menu.separator(),
+                    MenuItemSpec::Header { label } => menu.header(label),
+                    MenuItemSpec::Entry { label, action } => match cx.build_action(&action, None) {
+                        Ok(action) => menu.action(label, action, cx),
+                        Err(_) => menu,
+                    },
+                };
+            }
+            menu
         })
     }
+}
+
+impl<T: Clone + 'static> ContextMenu<T> {
+    /// Builds a menu whose `.value(...)` entries emit `T` via
+    /// [`MenuSelected`] on confirm or click. Requires an explicit type, e.g.
+    /// `ContextMenu::<MyValue>::build_typed(cx, |menu, cx| ...)`, since
+    /// nothing else pins `T` at the call site. Most menus should use
+    /// [`ContextMenu::build`] instead.
+    pub fn build_typed(
+        cx: &mut WindowContext,
+        f: impl FnOnce(Self, &mut WindowContext) -> Self,
+    ) -> View<Self> {
+        cx.build_view(|cx| f(Self::empty(cx), cx))
+    }
+
+    /// Clamps the menu's viewport to `height`, scrolling its contents instead
+    /// of letting the menu grow past it. Leaving this unset preserves the
+    /// previous unbounded behavior.
+    pub fn max_height(mut self, height: Pixels) -> Self {
+        self.max_height = Some(height);
+        self
+    }
+
+    /// Opts this menu into type-ahead search: printable keystrokes narrow the
+    /// visible items to those whose label fuzzy-matches what's been typed so
+    /// far, and backspace widens it again. Off by default, since most menus
+    /// are short enough that filtering would just intercept keystrokes (e.g.
+    /// mnemonics) meant for something else.
+    pub fn filterable(mut self) -> Self {
+        self.filterable = true;
+        self
+    }
 
     pub fn header(mut self, title: impl Into<SharedString>) -> Self {
         self.items.push(ContextMenuItem::Header(title.into()));
@@ -69,6 +166,22 @@ impl ContextMenu {
             label: label.into(),
             handler: Rc::new(on_click),
             key_binding: None,
+            disabled: false,
+        });
+        self
+    }
+
+    /// Adds an entry that is visible but greyed-out and cannot be confirmed
+    /// or clicked, for commands that are conditionally unavailable but
+    /// should still appear in the menu. Takes no `on_click`: disabled entries
+    /// are unselectable and return early on click, so a handler could never
+    /// fire.
+    pub fn entry_disabled(mut self, label: impl Into<SharedString>) -> Self {
+        self.items.push(ContextMenuItem::Entry {
+            label: label.into(),
+            handler: Rc::new(|_| {}),
+            key_binding: None,
+            disabled: true,
         });
         self
     }
@@ -83,46 +196,107 @@ impl ContextMenu {
             label: label.into(),
             key_binding: KeyBinding::for_action(&*action, cx),
             handler: Rc::new(move |cx| cx.dispatch_action(action.boxed_clone())),
+            disabled: false,
+        });
+        self
+    }
+
+    /// Adds a checkbox-style entry that renders a check indicator reflecting
+    /// `checked` (e.g. "Word Wrap ✓"). The menu itself flips the stored
+    /// `checked` value and re-renders on confirm or click, then invokes
+    /// `on_toggle` so the caller can apply the change to its own backing
+    /// state. Unlike other entries, toggling does not dismiss the menu.
+    pub fn toggle(
+        mut self,
+        label: impl Into<SharedString>,
+        checked: bool,
+        on_toggle: impl Fn(&mut WindowContext) + 'static,
+    ) -> Self {
+        self.items.push(ContextMenuItem::Toggle {
+            label: label.into(),
+            checked,
+            handler: Rc::new(on_toggle),
+        });
+        self
+    }
+
+    /// Adds an entry that, when confirmed or clicked, emits `value` to the
+    /// menu's parent via [`MenuSelected`] instead of invoking a closure. This
+    /// suits pickers that just want "the user chose X" without threading
+    /// mutable state through a captured callback.
+    pub fn value(mut self, label: impl Into<SharedString>, value: T) -> Self {
+        self.items.push(ContextMenuItem::Value {
+            label: label.into(),
+            value,
         });
         self
     }
 
     pub fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
-        if let Some(ContextMenuItem::Entry { handler, .. }) =
-            self.selected_index.and_then(|ix| self.items.get(ix))
-        {
-            (handler)(cx)
+        let Some(ix) = self.selected_index else {
+            cx.emit(DismissEvent);
+            return;
+        };
+
+        // Toggling is handled separately since it mutates `self.items`
+        // in place rather than dismissing the menu.
+        if matches!(self.items.get(ix), Some(ContextMenuItem::Toggle { .. })) {
+            self.toggle_at(ix, cx);
+            return;
+        }
+
+        match self.items.get(ix) {
+            Some(ContextMenuItem::Entry { handler, .. }) => (handler)(cx),
+            Some(ContextMenuItem::Value { value, .. }) => {
+                let value = value.clone();
+                cx.emit(MenuSelected(value));
+            }
+            _ => {}
         }
         cx.emit(DismissEvent);
     }
 
+    /// Flips the `checked` state stored on the `Toggle` entry at `ix`,
+    /// invokes its handler so the caller can react, and notifies so the `✓`
+    /// updates in place. Unlike other entries, toggling doesn't dismiss the
+    /// menu: the menu itself owns the checked state shown next render.
+    fn toggle_at(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        if let Some(ContextMenuItem::Toggle {
+            checked, handler, ..
+        }) = self.items.get_mut(ix)
+        {
+            *checked = !*checked;
+            let handler = handler.clone();
+            handler(cx);
+        }
+        cx.notify();
+    }
+
     pub fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
         cx.emit(DismissEvent);
     }
 
     fn select_first(&mut self, _: &SelectFirst, cx: &mut ViewContext<Self>) {
-        self.selected_index = self.items.iter().position(|item| item.is_selectable());
+        let visible = self.visible_items();
+        self.selected_index = self.selectable_index(&visible, 0..self.items.len());
+        self.scroll_to_selected_index(&visible);
         cx.notify();
     }
 
     fn select_last(&mut self, _: &SelectLast, cx: &mut ViewContext<Self>) {
-        for (ix, item) in self.items.iter().enumerate().rev() {
-            if item.is_selectable() {
-                self.selected_index = Some(ix);
-                cx.notify();
-                break;
-            }
-        }
+        let visible = self.visible_items();
+        self.selected_index = self.selectable_index(&visible, (0..self.items.len()).rev());
+        self.scroll_to_selected_index(&visible);
+        cx.notify();
     }
 
     fn select_next(&mut self, _: &SelectNext, cx: &mut ViewContext<Self>) {
         if let Some(ix) = self.selected_index {
-            for (ix, item) in self.items.iter().enumerate().skip(ix + 1) {
-                if item.is_selectable() {
-                    self.selected_index = Some(ix);
-                    cx.notify();
-                    break;
-                }
+            let visible = self.visible_items();
+            if let Some(next) = self.selectable_index(&visible, ix + 1..self.items.len()) {
+                self.selected_index = Some(next);
+                self.scroll_to_selected_index(&visible);
+                cx.notify();
             }
         } else {
             self.select_first(&Default::default(), cx);
@@ -131,35 +305,314 @@ impl ContextMenu {
 
     pub fn select_prev(&mut self, _: &SelectPrev, cx: &mut ViewContext<Self>) {
         if let Some(ix) = self.selected_index {
-            for (ix, item) in self.items.iter().enumerate().take(ix).rev() {
-                if item.is_selectable() {
-                    self.selected_index = Some(ix);
-                    cx.notify();
-                    break;
-                }
+            let visible = self.visible_items();
+            if let Some(prev) = self.selectable_index(&visible, (0..ix).rev()) {
+                self.selected_index = Some(prev);
+                self.scroll_to_selected_index(&visible);
+                cx.notify();
             }
         } else {
             self.select_last(&Default::default(), cx);
         }
     }
+
+    /// Scrolls the currently selected entry into the viewport after the
+    /// selection changes (keyboard navigation or a new filter); scrolling the
+    /// menu itself does not call this. `scroll_to_item` addresses rendered
+    /// children by position, which
+    /// only matches `selected_index` (an index into the full `items` vec)
+    /// when nothing is filtered out, so this maps it to its position among
+    /// the currently visible items first.
+    fn scroll_to_selected_index(&self, visible: &[bool]) {
+        if let Some(ix) = self.selected_index {
+            let position = self.items[..ix]
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| visible[*i])
+                .count();
+            self.scroll_handle.scroll_to_item(position);
+        }
+    }
+
+    fn selectable_index(
+        &self,
+        visible: &[bool],
+        indices: impl Iterator<Item = usize>,
+    ) -> Option<usize> {
+        indices
+            .filter(|&ix| visible[ix] && self.items[ix].is_selectable())
+            .next()
+    }
+
+    /// Accumulates printable input into `filter` and backspace to shrink it,
+    /// narrowing `items` to labels that fuzzy-match the query and snapping
+    /// `selected_index` to the first match. Navigation then operates only
+    /// over this filtered set. Does nothing unless [`Self::filterable`] was
+    /// set, and ignores a keystroke that would leave nothing visible at all,
+    /// rather than let the menu go blank.
+    fn handle_key_down(&mut self, event: &KeyDownEvent, cx: &mut ViewContext<Self>) {
+        if !self.filterable {
+            return;
+        }
+
+        let key = &event.keystroke.key;
+        if key == "backspace" {
+            if self.filter.pop().is_some() {
+                self.refine_filter(cx);
+            }
+            return;
+        }
+
+        let modifiers = &event.keystroke.modifiers;
+        if !modifiers.control && !modifiers.alt && !modifiers.platform {
+            if let Some(input) = event
+                .keystroke
+                .ime_key
+                .as_ref()
+                .filter(|s| s.chars().count() == 1 && !s.chars().next().unwrap().is_control())
+            {
+                let mut candidate = self.filter.clone();
+                candidate.push_str(input);
+                if self.visible_items_for(&candidate).iter().any(|v| *v) {
+                    self.filter = candidate;
+                    self.refine_filter(cx);
+                }
+            }
+        }
+    }
+
+    fn refine_filter(&mut self, cx: &mut ViewContext<Self>) {
+        let visible = self.visible_items();
+        self.selected_index = self.selectable_index(&visible, 0..self.items.len());
+        self.scroll_to_selected_index(&visible);
+        cx.notify();
+    }
+
+    /// Returns, for every item, whether it should currently be shown under
+    /// `self.filter`. See [`Self::visible_items_for`].
+    fn visible_items(&self) -> Vec<bool> {
+        self.visible_items_for(&self.filter)
+    }
+
+    /// Returns, for every item, whether it should be shown under `filter`.
+    /// Takes `filter` explicitly (rather than always reading `self.filter`)
+    /// so callers can preview the effect of a candidate filter before
+    /// committing to it. See [`visible_items_for_filter`] for the logic.
+    fn visible_items_for(&self, filter: &str) -> Vec<bool> {
+        visible_items_for_filter(&self.items, filter)
+    }
+}
+
+/// Returns, for every item in `items`, whether it should currently be shown
+/// under `filter`: entries match `filter` directly, while headers and
+/// separators are shown only if a matching entry remains beneath them before
+/// the next header. A free function (rather than a method) so it's testable
+/// without building a full [`ContextMenu`].
+fn visible_items_for_filter<T>(items: &[ContextMenuItem<T>], filter: &str) -> Vec<bool> {
+    if filter.is_empty() {
+        return vec![true; items.len()];
+    }
+
+    let mut visible: Vec<bool> = items
+        .iter()
+        .map(|item| match item.label() {
+            Some(label) => fuzzy_match(label, filter),
+            None => false,
+        })
+        .collect();
+
+    for ix in (0..items.len()).rev() {
+        if matches!(
+            items[ix],
+            ContextMenuItem::Header(_) | ContextMenuItem::Separator
+        ) {
+            visible[ix] = items[ix + 1..]
+                .iter()
+                .take_while(|item| !matches!(item, ContextMenuItem::Header(_)))
+                .zip(&visible[ix + 1..])
+                .any(|(_, &item_visible)| item_visible);
+        }
+    }
+
+    visible
 }
 
-impl ContextMenuItem {
+impl<T> ContextMenuItem<T> {
     fn is_selectable(&self) -> bool {
-        matches!(self, Self::Entry { .. })
+        match self {
+            Self::Entry { disabled, .. } => !disabled,
+            Self::Value { .. } | Self::Toggle { .. } => true,
+            Self::Separator | Self::Header(_) => false,
+        }
+    }
+
+    fn label(&self) -> Option<&SharedString> {
+        match self {
+            Self::Entry { label, .. } | Self::Value { label, .. } | Self::Toggle { label, .. } => {
+                Some(label)
+            }
+            Self::Separator | Self::Header(_) => None,
+        }
+    }
+}
+
+/// A lightweight subsequence matcher: `query`'s characters must all appear in
+/// `label`, in order, case-insensitively, though not necessarily contiguous.
+fn fuzzy_match(label: &str, query: &str) -> bool {
+    let mut query_chars = query.chars().map(|c| c.to_ascii_lowercase());
+    let mut next = query_chars.next();
+    for c in label.chars().map(|c| c.to_ascii_lowercase()) {
+        let Some(q) = next else { break };
+        if c == q {
+            next = query_chars.next();
+        }
+    }
+    next.is_none()
+}
+
+/// Returns the `char` indices in `label` that make up the first subsequence
+/// match of `query`, for highlighting.
+fn fuzzy_match_positions(label: &str, query: &str) -> Vec<usize> {
+    let mut query_chars: Vec<char> = query.chars().map(|c| c.to_ascii_lowercase()).collect();
+    query_chars.reverse();
+    let mut positions = Vec::new();
+    let mut next = query_chars.pop();
+    for (ix, c) in label.chars().map(|c| c.to_ascii_lowercase()).enumerate() {
+        let Some(q) = next else { break };
+        if c == q {
+            positions.push(ix);
+            next = query_chars.pop();
+        }
+    }
+    positions
+}
+
+/// Renders `label`, coloring the runs matched by the active type-ahead
+/// `filter` (if any) so the match is visible at a glance. Matched and
+/// unmatched characters are grouped into contiguous runs rather than one
+/// `Label` per character, so normal text shaping (kerning, spacing) is
+/// preserved within each run. Uses a plain flex row, not `h_stack`, so no
+/// gap is introduced between adjacent runs.
+fn render_label(label: &SharedString, filter: &str, disabled: bool) -> Div {
+    let base_color = if disabled {
+        Color::Disabled
+    } else {
+        Color::Default
+    };
+
+    if filter.is_empty() {
+        return div().child(Label::new(label.clone()).color(base_color));
     }
+
+    let matched = fuzzy_match_positions(label, filter);
+    let mut runs: Vec<(String, bool)> = Vec::new();
+    for (ix, c) in label.chars().enumerate() {
+        let is_match = matched.contains(&ix);
+        match runs.last_mut() {
+            Some((text, last_is_match)) if *last_is_match == is_match => text.push(c),
+            _ => runs.push((c.to_string(), is_match)),
+        }
+    }
+
+    div()
+        .flex()
+        .flex_row()
+        .children(runs.into_iter().map(|(text, is_match)| {
+            let color = if is_match { Color::Accent } else { base_color };
+            Label::new(text).color(color)
+        }))
 }
 
-impl Render for ContextMenu {
+impl<T: Clone + 'static> Render for ContextMenu<T> {
     type Element = Div;
 
     fn render(&mut self, cx: &mut ViewContext<Self>) -> Self::Element {
+        let visible = self.visible_items();
+        let filter = self.filter.clone();
+
+        let items: Vec<_> = self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(ix, _)| visible[*ix])
+            .map(|(ix, item)| match item {
+                ContextMenuItem::Separator => ListSeparator.into_any_element(),
+                ContextMenuItem::Header(header) => {
+                    ListSubHeader::new(header.clone()).into_any_element()
+                }
+                ContextMenuItem::Entry {
+                    label: entry,
+                    handler: callback,
+                    key_binding,
+                    disabled,
+                } => {
+                    let callback = callback.clone();
+                    let disabled = *disabled;
+                    let dismiss = cx.listener(|_, _, cx| cx.emit(DismissEvent));
+
+                    ListItem::new(entry.clone())
+                        .child(
+                            h_stack()
+                                .w_full()
+                                .justify_between()
+                                .child(render_label(entry, &filter, disabled))
+                                .children(
+                                    key_binding
+                                        .clone()
+                                        .map(|binding| div().ml_1().child(binding)),
+                                ),
+                        )
+                        .selected(Some(ix) == self.selected_index)
+                        .on_click(move |event, cx| {
+                            if disabled {
+                                return;
+                            }
+                            callback(cx);
+                            dismiss(event, cx)
+                        })
+                        .into_any_element()
+                }
+                ContextMenuItem::Toggle { label, checked, .. } => {
+                    let checked = *checked;
+
+                    ListItem::new(label.clone())
+                        .child(
+                            h_stack()
+                                .w_full()
+                                .justify_between()
+                                .child(render_label(label, &filter, false))
+                                .child(div().w_4().child(if checked { "✓" } else { "" })),
+                        )
+                        .selected(Some(ix) == self.selected_index)
+                        // The menu owns the checked state: clicking flips it
+                        // on `self.items` and notifies, rather than relying
+                        // on the caller to re-render with a new value.
+                        .on_click(cx.listener(move |this, _, cx| this.toggle_at(ix, cx)))
+                        .into_any_element()
+                }
+                ContextMenuItem::Value { label, value } => {
+                    let value = value.clone();
+                    let dismiss = cx.listener(|_, _, cx| cx.emit(DismissEvent));
+
+                    ListItem::new(label.clone())
+                        .child(render_label(label, &filter, false))
+                        .selected(Some(ix) == self.selected_index)
+                        .on_click(move |event, cx| {
+                            cx.emit(MenuSelected(value.clone()));
+                            dismiss(event, cx)
+                        })
+                        .into_any_element()
+                }
+            })
+            .collect();
+
         div().elevation_2(cx).flex().flex_row().child(
             v_stack()
                 .min_w(px(200.))
                 .track_focus(&self.focus_handle)
                 .on_mouse_down_out(cx.listener(|this, _, cx| this.cancel(&Default::default(), cx)))
                 .key_context("menu")
+                .on_key_down(cx.listener(ContextMenu::handle_key_down))
                 .on_action(cx.listener(ContextMenu::select_first))
                 .on_action(cx.listener(ContextMenu::select_last))
                 .on_action(cx.listener(ContextMenu::select_next))
@@ -168,42 +621,104 @@ impl Render for ContextMenu {
                 .on_action(cx.listener(ContextMenu::cancel))
                 .flex_none()
                 .child(
-                    List::new().children(self.items.iter().enumerate().map(
-                        |(ix, item)| match item {
-                            ContextMenuItem::Separator => ListSeparator.into_any_element(),
-                            ContextMenuItem::Header(header) => {
-                                ListSubHeader::new(header.clone()).into_any_element()
-                            }
-                            ContextMenuItem::Entry {
-                                label: entry,
-                                handler: callback,
-                                key_binding,
-                            } => {
-                                let callback = callback.clone();
-                                let dismiss = cx.listener(|_, _, cx| cx.emit(DismissEvent));
-
-                                ListItem::new(entry.clone())
-                                    .child(
-                                        h_stack()
-                                            .w_full()
-                                            .justify_between()
-                                            .child(Label::new(entry.clone()))
-                                            .children(
-                                                key_binding
-                                                    .clone()
-                                                    .map(|binding| div().ml_1().child(binding)),
-                                            ),
-                                    )
-                                    .selected(Some(ix) == self.selected_index)
-                                    .on_click(move |event, cx| {
-                                        callback(cx);
-                                        dismiss(event, cx)
-                                    })
-                                    .into_any_element()
-                            }
-                        },
-                    )),
+                    // The scroll handle tracks each item as a direct child here
+                    // (rather than a single wrapping list), since
+                    // `ScrollHandle::scroll_to_item` addresses direct children.
+                    div()
+                        .when_some(self.max_height, |this, max_height| {
+                            this.max_h(max_height)
+                                .overflow_y_scroll()
+                                .track_scroll(&self.scroll_handle)
+                        })
+                        .children(items),
                 ),
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_match_requires_in_order_subsequence() {
+        assert!(fuzzy_match("Toggle Word Wrap", "tww"));
+        assert!(fuzzy_match("Toggle Word Wrap", "Wrap"));
+        assert!(fuzzy_match("Toggle Word Wrap", ""));
+        assert!(!fuzzy_match("Toggle Word Wrap", "wwt"));
+        assert!(!fuzzy_match("Toggle Word Wrap", "xyz"));
+    }
+
+    #[test]
+    fn fuzzy_match_is_case_insensitive() {
+        assert!(fuzzy_match("Save As...", "SAVE"));
+        assert!(fuzzy_match("Save As...", "save as"));
+    }
+
+    #[test]
+    fn fuzzy_match_positions_returns_first_match_indices() {
+        assert_eq!(
+            fuzzy_match_positions("Toggle Word Wrap", "tww"),
+            vec![0, 7, 12]
+        );
+        assert_eq!(
+            fuzzy_match_positions("Save As...", "save"),
+            vec![0, 1, 2, 3]
+        );
+        assert_eq!(
+            fuzzy_match_positions("Save As...", "xyz"),
+            Vec::<usize>::new()
+        );
+    }
+
+    fn entry(label: &str) -> ContextMenuItem<()> {
+        ContextMenuItem::Entry {
+            label: label.to_string().into(),
+            handler: Rc::new(|_| {}),
+            key_binding: None,
+            disabled: false,
+        }
+    }
+
+    #[test]
+    fn visible_items_for_filter_empty_shows_everything() {
+        let items = vec![ContextMenuItem::Header("Section".into()), entry("Copy")];
+        assert_eq!(visible_items_for_filter(&items, ""), vec![true, true]);
+    }
+
+    #[test]
+    fn visible_items_for_filter_hides_non_matching_entries() {
+        let items = vec![entry("Copy"), entry("Paste")];
+        assert_eq!(visible_items_for_filter(&items, "cop"), vec![true, false]);
+    }
+
+    #[test]
+    fn visible_items_for_filter_hides_header_with_no_matches_beneath() {
+        let items = vec![
+            ContextMenuItem::Header("Edit".into()),
+            entry("Copy"),
+            entry("Paste"),
+            ContextMenuItem::Header("View".into()),
+            entry("Zoom In"),
+        ];
+        assert_eq!(
+            visible_items_for_filter(&items, "zoom"),
+            vec![false, false, false, true, true]
+        );
+    }
+
+    #[test]
+    fn visible_items_for_filter_keeps_header_with_a_match_beneath() {
+        let items = vec![ContextMenuItem::Header("Edit".into()), entry("Copy")];
+        assert_eq!(visible_items_for_filter(&items, "copy"), vec![true, true]);
+    }
+
+    #[test]
+    fn visible_items_for_filter_hides_separator_with_no_matches_beneath_it() {
+        let items = vec![entry("Copy"), ContextMenuItem::Separator, entry("Paste")];
+        assert_eq!(
+            visible_items_for_filter(&items, "copy"),
+            vec![true, false, false]
+        );
+    }
+}